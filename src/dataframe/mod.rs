@@ -6,22 +6,33 @@ use rayon::prelude::*;
 
 pub use crate::traits::*;
 
+pub mod traits;
+
 /// Holds a collection of your `Records`.
+#[derive(Debug, PartialEq)]
 pub struct DataFrame<R: Record>
 {
     pub(crate) rows: Vec<R>,
 }
 
-impl<R: Record> DataFrame<R>
+impl<R: Record> Default for DataFrame<R>
 {
-    /// Create an empty dataframe.
-    pub fn new() -> Self
+    fn default() -> Self
     {
         Self
         {
             rows: Vec::new(),
         }
     }
+}
+
+impl<R: Record> DataFrame<R>
+{
+    /// Create an empty dataframe.
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
 
     /// Construct dataframe with the constructor, using values from the local
     /// environment.
@@ -36,6 +47,18 @@ impl<R: Record> DataFrame<R>
         self.rows.push(r);
     }
 
+    /// Number of rows in this dataframe.
+    pub fn len(&self) -> usize
+    {
+        self.rows.len()
+    }
+
+    /// Whether this dataframe has no rows.
+    pub fn is_empty(&self) -> bool
+    {
+        self.rows.is_empty()
+    }
+
     /// Find rows in `self` and `other` which satisfy a predicate, then
     /// perform some action with the matches.
     ///
@@ -80,16 +103,10 @@ impl<R: Record> DataFrame<R>
                         Some(transform((s.clone(), item.clone())))
                     }
 
-                    // otherwise use ctor if provided
-                    else if let Some(ref ctor) = constructor
-                    {
-                        Some(ctor())
-                    }
-
-                    // otherwise skip
+                    // otherwise use ctor if provided, else skip
                     else
                     {
-                        None
+                        constructor.as_ref().map(|ctor| ctor())
                     }
                 })
                 .collect()
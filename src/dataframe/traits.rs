@@ -1,7 +1,7 @@
 //! Trait implementations for `DataFrame`.
 
-use dataframe::DataFrame;
-use traits::*;
+use crate::dataframe::DataFrame;
+use crate::traits::*;
 
 use std::iter::Extend;
 
@@ -16,4 +16,22 @@ impl<R: Record> Extend<R> for DataFrame<R>
     }
 }
 
+/// A dataframe (de)serializes as the plain sequence of its records, same as
+/// the `Vec<R>` it wraps.
+impl<R: Record + serde::Serialize> serde::Serialize for DataFrame<R>
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        self.rows.serialize(serializer)
+    }
+}
+
+impl<'de, R: Record + serde::Deserialize<'de>> serde::Deserialize<'de> for DataFrame<R>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        Vec::<R>::deserialize(deserializer).map(|rows| DataFrame { rows })
+    }
+}
+
 
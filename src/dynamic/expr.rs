@@ -0,0 +1,466 @@
+//! Expression engine backing `DataFrame::filter`.
+//!
+//! This finishes the "notepad" idea sketched out in `df_token.rs`: the user's
+//! closure is handed a [`DfToken`], and instead of computing anything
+//! directly, indexing and operator overloading record an expression tree (an
+//! RPN stack under the hood, same as the prototype) in a `RefCell`. Once the
+//! closure returns, `filter` pulls the tree back out and runs it in two
+//! passes:
+//!
+//! 1. **typecheck** - walk the tree bottom-up, assigning each node a
+//!    [`Ty`] and rejecting anything that doesn't make sense (e.g. comparing
+//!    a `Factor` to a `Float`), exactly like `rbind_ck` rejects mismatched
+//!    columns.
+//! 2. **evaluate** - only once typecheck has passed, walk the tree once per
+//!    row (in parallel, via rayon) to produce the boolean mask that rows are
+//!    kept or dropped by.
+
+use std::cell::RefCell;
+use std::ops::{Add, Mul, Sub};
+use std::rc::Rc;
+
+use rayon::prelude::*;
+
+use super::error_string::ErrorString;
+use super::{Column, DataFrame, Result};
+
+/// A node in the expression tree recorded by a [`DfToken`].
+#[derive(Debug, Clone)]
+pub(crate) enum Node
+{
+    OwnColumn(String),
+    Const(f64),
+    Add(Box<Node>, Box<Node>),
+    Sub(Box<Node>, Box<Node>),
+    Mul(Box<Node>, Box<Node>),
+    Lt(Box<Node>, Box<Node>),
+    Gt(Box<Node>, Box<Node>),
+    Eq(Box<Node>, Box<Node>),
+}
+
+/// The type a node evaluates to, used by the typecheck pass.
+///
+/// Mirrors the `Column` variants, minus the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty
+{
+    Float,
+    Double,
+    Factor,
+    Bool,
+}
+
+impl Ty
+{
+    fn name(self) -> &'static str
+    {
+        match self
+        {
+            Ty::Float  => "Float",
+            Ty::Double => "Double",
+            Ty::Factor => "Factor",
+            Ty::Bool   => "Bool",
+        }
+    }
+}
+
+/// A value produced by the evaluate pass for a single row.
+enum Val
+{
+    Float(f32),
+    Double(f64),
+    Factor(String),
+    Bool(bool),
+}
+
+/// A value of this type is passed into the closure given to `DataFrame::filter`.
+///
+/// Indexing (`&token["Foo"]`) and the arithmetic/comparison operators below
+/// don't do any real work; they just push onto a shared RPN stack. Every
+/// `DfToken` derived from the same root (via `Clone`, which is cheap - it
+/// only bumps an `Rc`) shares that stack, so the whole expression recorded
+/// during one call to the closure ends up in one place, ready for
+/// [`DfToken::into_tree`] to turn into a single [`Node`].
+#[derive(Clone)]
+pub struct DfToken
+{
+    stack: Rc<RefCell<Vec<Node>>>,
+}
+
+impl DfToken
+{
+    pub(crate) fn new() -> Self
+    {
+        Self { stack: Rc::new(RefCell::new(Vec::new())) }
+    }
+
+    fn push(&self, node: Node)
+    {
+        self.stack.borrow_mut().push(node);
+    }
+
+    /// Pop the two most-recently-recorded operands off the shared stack and
+    /// push `ctor(lhs, rhs)` in their place, so that further chaining (e.g.
+    /// `&(&d["a"] + &d["b"]) + &d["c"]`) keeps working.
+    fn combine(&self, ctor: impl FnOnce(Box<Node>, Box<Node>) -> Node) -> Self
+    {
+        let mut stack = self.stack.borrow_mut();
+        let rhs = stack.pop().expect("dataframes1: filter expression is missing its right-hand operand");
+        let lhs = stack.pop().expect("dataframes1: filter expression is missing its left-hand operand");
+        stack.push(ctor(Box::new(lhs), Box::new(rhs)));
+        drop(stack);
+        self.clone()
+    }
+
+    /// Consume the token, returning the single expression tree it recorded.
+    ///
+    /// Errors (rather than panics, since this runs after the closure has
+    /// already returned) if the stack doesn't reduce to exactly one node -
+    /// either because nothing was ever indexed, or because more than one
+    /// unconsumed operand was left lying around.
+    pub(crate) fn into_tree(self) -> Result<Node>
+    {
+        let mut stack = match Rc::try_unwrap(self.stack)
+        {
+            Ok(cell) => cell.into_inner(),
+            Err(shared) => shared.borrow().clone(),
+        };
+
+        match (stack.pop(), stack.is_empty())
+        {
+            (Some(root), true) => Ok(root),
+            (None, _) => ErrorString::from("filter closure did not record any expression.").err(),
+            (Some(_), false) => ErrorString::from("filter closure left more than one unconsumed value on the expression stack.").err(),
+        }
+    }
+}
+
+impl<'a> ::std::ops::Index<&'a str> for DfToken
+{
+    type Output = Self;
+    fn index(&self, index: &'a str) -> &Self::Output
+    {
+        self.push(Node::OwnColumn(index.to_string()));
+        self
+    }
+}
+
+impl<'a> Add<&'a DfToken> for &DfToken
+{
+    type Output = DfToken;
+    fn add(self, _rhs: &'a DfToken) -> DfToken { self.combine(Node::Add) }
+}
+
+impl<'a> Sub<&'a DfToken> for &DfToken
+{
+    type Output = DfToken;
+    fn sub(self, _rhs: &'a DfToken) -> DfToken { self.combine(Node::Sub) }
+}
+
+impl<'a> Mul<&'a DfToken> for &DfToken
+{
+    type Output = DfToken;
+    fn mul(self, _rhs: &'a DfToken) -> DfToken { self.combine(Node::Mul) }
+}
+
+/// Lets `3 * &df["col"]` read as a scalar multiply, same as the
+/// `3 * df["qux"]` example in `df_token.rs`.
+impl Mul<&DfToken> for f64
+{
+    type Output = DfToken;
+    fn mul(self, rhs: &DfToken) -> DfToken
+    {
+        rhs.push(Node::Const(self));
+        rhs.combine(Node::Mul)
+    }
+}
+
+impl PartialEq<f64> for &DfToken
+{
+    fn eq(&self, other: &f64) -> bool
+    {
+        self.push(Node::Const(*other));
+        self.combine(Node::Eq);
+        true
+    }
+}
+
+impl PartialOrd<f64> for &DfToken
+{
+    fn partial_cmp(&self, _other: &f64) -> Option<::std::cmp::Ordering> { None }
+
+    fn lt(&self, other: &f64) -> bool
+    {
+        self.push(Node::Const(*other));
+        self.combine(Node::Lt);
+        true
+    }
+
+    fn gt(&self, other: &f64) -> bool
+    {
+        self.push(Node::Const(*other));
+        self.combine(Node::Gt);
+        true
+    }
+}
+
+/// Same operators as above, but taking an owned `DfToken` on the left.
+///
+/// Needed because `Add`/`Sub`/`Mul for &DfToken` return an owned `DfToken`
+/// (not a reference), so a combined expression like
+/// `&d["Foo"] + &d["Bar"] < 3` has an owned `DfToken` on the left of `<` -
+/// without these, that canonical example doesn't compile.
+///
+/// There's no `PartialEq`/`PartialOrd for &DfToken` taking a `&DfToken` rhs
+/// (like the `f64` ones above) because it'd conflict with the standard
+/// library's blanket `impl<A: PartialEq<B>> PartialEq<&B> for &A` - once
+/// `DfToken` implements `PartialEq<DfToken>`/`PartialOrd<DfToken>` below,
+/// that blanket already covers `&DfToken == &DfToken` for us.
+impl<'a> Add<&'a DfToken> for DfToken
+{
+    type Output = DfToken;
+    fn add(self, rhs: &'a DfToken) -> DfToken { (&self).add(rhs) }
+}
+
+impl<'a> Sub<&'a DfToken> for DfToken
+{
+    type Output = DfToken;
+    fn sub(self, rhs: &'a DfToken) -> DfToken { (&self).sub(rhs) }
+}
+
+impl<'a> Mul<&'a DfToken> for DfToken
+{
+    type Output = DfToken;
+    fn mul(self, rhs: &'a DfToken) -> DfToken { (&self).mul(rhs) }
+}
+
+impl Mul<DfToken> for f64
+{
+    type Output = DfToken;
+    fn mul(self, rhs: DfToken) -> DfToken { self * &rhs }
+}
+
+impl PartialEq<f64> for DfToken
+{
+    fn eq(&self, other: &f64) -> bool { (&self).eq(other) }
+}
+
+impl PartialEq<DfToken> for DfToken
+{
+    fn eq(&self, _other: &DfToken) -> bool
+    {
+        self.combine(Node::Eq);
+        true
+    }
+}
+
+impl PartialOrd<f64> for DfToken
+{
+    fn partial_cmp(&self, _other: &f64) -> Option<::std::cmp::Ordering> { None }
+
+    fn lt(&self, other: &f64) -> bool { (&self).lt(other) }
+
+    fn gt(&self, other: &f64) -> bool { (&self).gt(other) }
+}
+
+impl PartialOrd<DfToken> for DfToken
+{
+    fn partial_cmp(&self, _other: &DfToken) -> Option<::std::cmp::Ordering> { None }
+
+    fn lt(&self, _other: &DfToken) -> bool
+    {
+        self.combine(Node::Lt);
+        true
+    }
+
+    fn gt(&self, _other: &DfToken) -> bool
+    {
+        self.combine(Node::Gt);
+        true
+    }
+}
+
+fn column_ty(df: &DataFrame, name: &str) -> Result<Ty>
+{
+    match df.columns.get(name)
+    {
+        Some(Column::Float(_))  => Ok(Ty::Float),
+        Some(Column::Double(_)) => Ok(Ty::Double),
+        Some(Column::Factor(_)) => Ok(Ty::Factor),
+        Some(Column::Bool(_))   => Ok(Ty::Bool),
+        None => ErrorString::from("Column `").p(name).p("` is not present in dataframe.").err(),
+    }
+}
+
+fn arithmetic_mismatch(a: Ty, b: Ty) -> Result<Ty>
+{
+    ErrorString::from("Cannot apply an arithmetic operator to a `")
+        .p(a.name())
+        .p("` and a `")
+        .p(b.name())
+        .p("` (columns must be the same numeric type; Float and Double do not mix implicitly).")
+        .err()
+}
+
+fn compare_mismatch(a: Ty, b: Ty) -> Result<Ty>
+{
+    ErrorString::from("Cannot compare a `")
+        .p(a.name())
+        .p("` to a `")
+        .p(b.name())
+        .p("`.")
+        .err()
+}
+
+/// A numeric literal (`Node::Const`) has no type of its own; it unifies with
+/// whichever numeric type (`Float` or `Double`) the other side of the
+/// operator turns out to be, the same way `3` unifies with either an `i32`
+/// or a `u8` in ordinary Rust arithmetic. Two literals together (unusual,
+/// but not rejected) default to `Double`.
+fn typecheck(node: &Node, df: &DataFrame) -> Result<Ty>
+{
+    match node
+    {
+        Node::Const(_) => Ok(Ty::Double),
+        Node::OwnColumn(name) => column_ty(df, name),
+
+        Node::Add(lhs, rhs) | Node::Sub(lhs, rhs) | Node::Mul(lhs, rhs) =>
+        {
+            match (lhs.as_ref(), rhs.as_ref())
+            {
+                (Node::Const(_), Node::Const(_)) => Ok(Ty::Double),
+
+                (Node::Const(_), _) => match typecheck(rhs, df)?
+                {
+                    t @ (Ty::Float | Ty::Double) => Ok(t),
+                    t => arithmetic_mismatch(Ty::Double, t),
+                },
+
+                (_, Node::Const(_)) => match typecheck(lhs, df)?
+                {
+                    t @ (Ty::Float | Ty::Double) => Ok(t),
+                    t => arithmetic_mismatch(t, Ty::Double),
+                },
+
+                _ => match (typecheck(lhs, df)?, typecheck(rhs, df)?)
+                {
+                    (Ty::Float, Ty::Float)   => Ok(Ty::Float),
+                    (Ty::Double, Ty::Double) => Ok(Ty::Double),
+                    (a, b) => arithmetic_mismatch(a, b),
+                },
+            }
+        }
+
+        Node::Lt(lhs, rhs) | Node::Gt(lhs, rhs) | Node::Eq(lhs, rhs) =>
+        {
+            match (lhs.as_ref(), rhs.as_ref())
+            {
+                (Node::Const(_), Node::Const(_)) => Ok(Ty::Bool),
+
+                (Node::Const(_), _) => match typecheck(rhs, df)?
+                {
+                    Ty::Float | Ty::Double => Ok(Ty::Bool),
+                    t => compare_mismatch(Ty::Double, t),
+                },
+
+                (_, Node::Const(_)) => match typecheck(lhs, df)?
+                {
+                    Ty::Float | Ty::Double => Ok(Ty::Bool),
+                    t => compare_mismatch(t, Ty::Double),
+                },
+
+                _ =>
+                {
+                    let lt = typecheck(lhs, df)?;
+                    let rt = typecheck(rhs, df)?;
+                    if lt == rt { Ok(Ty::Bool) } else { compare_mismatch(lt, rt) }
+                }
+            }
+        }
+    }
+}
+
+fn evaluate(node: &Node, df: &DataFrame, row: usize) -> Val
+{
+    match node
+    {
+        Node::Const(c) => Val::Double(*c),
+
+        Node::OwnColumn(name) => match &df.columns[name]
+        {
+            Column::Float(v)  => Val::Float(v[row]),
+            Column::Double(v) => Val::Double(v[row]),
+            Column::Factor(v) => Val::Factor(v[row].clone()),
+            Column::Bool(v)   => Val::Bool(v[row]),
+        },
+
+        // The `(Float, Double)`/`(Double, Float)` arms only ever fire when
+        // one side is a literal (`Node::Const` always evaluates to
+        // `Val::Double`) - typecheck still rejects two *columns* of
+        // different numeric type - so casting the literal down to `Float`
+        // here is exactly the cast `typecheck` implicitly allowed.
+        Node::Add(lhs, rhs) => match (evaluate(lhs, df, row), evaluate(rhs, df, row))
+        {
+            (Val::Float(a), Val::Float(b))   => Val::Float(a + b),
+            (Val::Double(a), Val::Double(b)) => Val::Double(a + b),
+            (Val::Float(a), Val::Double(b))  => Val::Float(a + b as f32),
+            (Val::Double(a), Val::Float(b))  => Val::Float(a as f32 + b),
+            _ => unreachable!("typecheck guarantees numeric operands"),
+        },
+
+        Node::Sub(lhs, rhs) => match (evaluate(lhs, df, row), evaluate(rhs, df, row))
+        {
+            (Val::Float(a), Val::Float(b))   => Val::Float(a - b),
+            (Val::Double(a), Val::Double(b)) => Val::Double(a - b),
+            (Val::Float(a), Val::Double(b))  => Val::Float(a - b as f32),
+            (Val::Double(a), Val::Float(b))  => Val::Float(a as f32 - b),
+            _ => unreachable!("typecheck guarantees numeric operands"),
+        },
+
+        Node::Mul(lhs, rhs) => match (evaluate(lhs, df, row), evaluate(rhs, df, row))
+        {
+            (Val::Float(a), Val::Float(b))   => Val::Float(a * b),
+            (Val::Double(a), Val::Double(b)) => Val::Double(a * b),
+            (Val::Float(a), Val::Double(b))  => Val::Float(a * b as f32),
+            (Val::Double(a), Val::Float(b))  => Val::Float(a as f32 * b),
+            _ => unreachable!("typecheck guarantees numeric operands"),
+        },
+
+        Node::Lt(lhs, rhs) => Val::Bool(compare(lhs, rhs, df, row) == Some(::std::cmp::Ordering::Less)),
+        Node::Gt(lhs, rhs) => Val::Bool(compare(lhs, rhs, df, row) == Some(::std::cmp::Ordering::Greater)),
+        Node::Eq(lhs, rhs) => Val::Bool(compare(lhs, rhs, df, row) == Some(::std::cmp::Ordering::Equal)),
+    }
+}
+
+fn compare(lhs: &Node, rhs: &Node, df: &DataFrame, row: usize) -> Option<::std::cmp::Ordering>
+{
+    match (evaluate(lhs, df, row), evaluate(rhs, df, row))
+    {
+        (Val::Float(a), Val::Float(b))   => a.partial_cmp(&b),
+        (Val::Double(a), Val::Double(b)) => a.partial_cmp(&b),
+        (Val::Float(a), Val::Double(b))  => a.partial_cmp(&(b as f32)),
+        (Val::Double(a), Val::Float(b))  => (a as f32).partial_cmp(&b),
+        (Val::Factor(a), Val::Factor(b)) => a.partial_cmp(&b),
+        (Val::Bool(a), Val::Bool(b))     => a.partial_cmp(&b),
+        _ => unreachable!("typecheck guarantees matching operand types"),
+    }
+}
+
+/// Run the typecheck-then-evaluate pipeline described above, returning the
+/// boolean mask of which rows to keep.
+pub(crate) fn filter_mask(tree: &Node, df: &DataFrame) -> Result<Vec<bool>>
+{
+    if typecheck(tree, df)? != Ty::Bool
+    {
+        return ErrorString::from("filter expression must evaluate to a boolean.").err();
+    }
+
+    Ok((0..df.nrow)
+        .into_par_iter()
+        .map(|row| match evaluate(tree, df, row)
+        {
+            Val::Bool(b) => b,
+            _ => unreachable!("typecheck guarantees a boolean root"),
+        })
+        .collect())
+}
@@ -0,0 +1,179 @@
+//! `group_by`/`summarise` aggregation pipeline.
+//!
+//! [`DataFrame::group_by`] partitions rows into a [`GroupedDataFrame`] by the
+//! distinct values of one or more `Factor`/`Bool` key columns. Calling
+//! [`GroupedDataFrame::summarise`] with a set of named [`Reducer`]s then
+//! produces one output row per group: the key columns, plus one column per
+//! reducer. Each group is reduced independently, so the reduction itself is
+//! parallelized with rayon.
+
+use rayon::prelude::*;
+
+use super::error_string::ErrorString;
+use super::{Column, DataFrame, Result};
+
+impl DataFrame
+{
+    /// Partition rows of `self` by the distinct values of `keys`, ready for
+    /// [`GroupedDataFrame::summarise`].
+    ///
+    /// `keys` must name `Factor` or `Bool` columns; grouping by a numeric
+    /// column is almost always a mistake (float equality), so it's rejected
+    /// up front rather than silently doing the wrong thing.
+    pub fn group_by(&self, keys: &[&str]) -> Result<GroupedDataFrame<'_>>
+    {
+        for key in keys
+        {
+            match self.columns.get(*key)
+            {
+                Some(Column::Factor(_)) | Some(Column::Bool(_)) => {}
+
+                Some(col) => return ErrorString::from("Cannot group by column `")
+                    .p(key)
+                    .p("`, which is a `")
+                    .p(col.variant_str())
+                    .p("` (only Factor and Bool columns can be grouped).")
+                    .err(),
+
+                None => return ErrorString::from("Column `").p(key).p("` is not present in dataframe.").err(),
+            }
+        }
+
+        let mut index: std::collections::HashMap<Vec<String>, usize> = std::collections::HashMap::new();
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+
+        for row in 0..self.nrow
+        {
+            let key = self.row_key(keys, row);
+            match index.get(&key)
+            {
+                Some(&i) => groups[i].push(row),
+                None =>
+                {
+                    index.insert(key, groups.len());
+                    groups.push(vec![row]);
+                }
+            }
+        }
+
+        Ok(GroupedDataFrame
+        {
+            df: self,
+            keys: keys.iter().map(|s| s.to_string()).collect(),
+            groups,
+        })
+    }
+}
+
+/// The result of [`DataFrame::group_by`]: a partitioning of a dataframe's
+/// rows by distinct key values, not yet reduced to anything.
+pub struct GroupedDataFrame<'a>
+{
+    df: &'a DataFrame,
+    keys: Vec<String>,
+    groups: Vec<Vec<usize>>,
+}
+
+/// A named aggregation over a numeric column, for use with
+/// [`GroupedDataFrame::summarise`]. `Count` doesn't need a column since it
+/// just counts rows per group.
+pub enum Reducer<'a>
+{
+    Sum(&'a str),
+    Mean(&'a str),
+    Min(&'a str),
+    Max(&'a str),
+    Count,
+}
+
+impl<'a> Reducer<'a>
+{
+    fn column(&self) -> Option<&'a str>
+    {
+        match self
+        {
+            Reducer::Sum(c) | Reducer::Mean(c) | Reducer::Min(c) | Reducer::Max(c) => Some(c),
+            Reducer::Count => None,
+        }
+    }
+
+    fn reduce(&self, df: &DataFrame, rows: &[usize]) -> f64
+    {
+        match self
+        {
+            Reducer::Count => rows.len() as f64,
+            Reducer::Sum(c) => column_values(df, c, rows).sum(),
+
+            Reducer::Mean(c) =>
+            {
+                let values: Vec<f64> = column_values(df, c, rows).collect();
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+
+            Reducer::Min(c) => column_values(df, c, rows).fold(f64::INFINITY, f64::min),
+            Reducer::Max(c) => column_values(df, c, rows).fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Read `rows` out of column `name` as `f64`, widening `Float` as needed.
+fn column_values<'b>(df: &'b DataFrame, name: &'b str, rows: &'b [usize]) -> impl Iterator<Item = f64> + 'b
+{
+    rows.iter().map(move |&row| match &df.columns[name]
+    {
+        Column::Float(v)  => v[row] as f64,
+        Column::Double(v) => v[row],
+        _ => unreachable!("summarise validated this column is numeric"),
+    })
+}
+
+impl<'a> GroupedDataFrame<'a>
+{
+    /// Reduce each group with `reducers`, producing a `DataFrame` with one
+    /// row per group: the key columns, plus one `Double` column per named
+    /// reducer.
+    pub fn summarise(&self, reducers: &[(&str, Reducer)]) -> Result<DataFrame>
+    {
+        for (_, reducer) in reducers
+        {
+            if let Some(name) = reducer.column()
+            {
+                match self.df.columns.get(name)
+                {
+                    Some(Column::Float(_)) | Some(Column::Double(_)) => {}
+
+                    Some(col) => return ErrorString::from("Cannot reduce column `")
+                        .p(name)
+                        .p("`, which is a `")
+                        .p(col.variant_str())
+                        .p("` (reducers need a numeric column).")
+                        .err(),
+
+                    None => return ErrorString::from("Column `").p(name).p("` is not present in dataframe.").err(),
+                }
+            }
+        }
+
+        // one representative row per group, to read the key values back out.
+        let representative: Vec<Option<usize>> = self.groups.iter().map(|rows| Some(rows[0])).collect();
+
+        let mut result = DataFrame
+        {
+            nrow: self.groups.len(),
+            ..DataFrame::default()
+        };
+
+        for key in &self.keys
+        {
+            result.columns.insert(key.clone(), self.df.columns[key].gather(&representative));
+        }
+
+        for (name, reducer) in reducers
+        {
+            let values: Vec<f64> = self.groups.par_iter().map(|rows| reducer.reduce(self.df, rows)).collect();
+            result.columns.insert((*name).to_string(), Column::Double(values));
+        }
+
+        Ok(result)
+    }
+}
@@ -4,7 +4,15 @@
 
 use std::collections::HashMap;
 use std::iter::Extend;
+use crate::traits::Value;
 use self::error_string::ErrorString;
+pub use self::expr::DfToken;
+pub use self::group::{GroupedDataFrame, Reducer};
+pub use self::source::{Csv, DataSource};
+
+mod expr;
+mod group;
+mod source;
 
 /// Represents a column in the dataframe.
 #[derive(Debug, Clone)]
@@ -18,35 +26,111 @@ enum Column
 
 impl Column
 {
-    /// Return the number of elements in the column.
-    fn len(&self) -> usize
+    /// Return the type of this column, as a str.
+    fn variant_str(&self) -> &'static str
+    {
+        use self::Column::*;
+        match *self
+        {
+            Float(_)  => "Float",
+            Double(_) => "Double",
+            Factor(_) => "Factor",
+            Bool(_)   => "Boolean",
+        }
+    }
+
+    /// Return a copy of this column containing only the rows for which
+    /// `mask` is `true`.
+    fn keep(&self, mask: &[bool]) -> Self
     {
         use self::Column::*;
         match self
         {
-            &Float(ref v)  => v.len(),
-            &Double(ref v) => v.len(),
-            &Factor(ref v) => v.len(),
-            &Bool(ref v)   => v.len(),
+            Float(v)  => Float(v.iter().zip(mask).filter(|(_, &keep)| keep).map(|(x, _)| *x).collect()),
+            Double(v) => Double(v.iter().zip(mask).filter(|(_, &keep)| keep).map(|(x, _)| *x).collect()),
+            Factor(v) => Factor(v.iter().zip(mask).filter(|(_, &keep)| keep).map(|(x, _)| x.clone()).collect()),
+            Bool(v)   => Bool(v.iter().zip(mask).filter(|(_, &keep)| keep).map(|(x, _)| *x).collect()),
         }
     }
 
-    /// Return the type of this column, as a str.
-    fn variant_str(&self) -> &'static str
+    /// Gather rows at `idx` into a new column, one output row per entry.
+    ///
+    /// `None` entries stand for "no matching row" (the unmatched side of a
+    /// `left_join`/`outer_join`) and become a null-ish placeholder, since
+    /// `Column` has no dedicated null representation: `NAN` for `Float` and
+    /// `Double`, an empty string for `Factor`, and `false` for `Bool`.
+    fn gather(&self, idx: &[Option<usize>]) -> Self
+    {
+        use self::Column::*;
+        match self
+        {
+            Float(v)  => Float(idx.iter().map(|o| o.map(|i| v[i]).unwrap_or(f32::NAN)).collect()),
+            Double(v) => Double(idx.iter().map(|o| o.map(|i| v[i]).unwrap_or(f64::NAN)).collect()),
+            Factor(v) => Factor(idx.iter().map(|o| o.map(|i| v[i].clone()).unwrap_or_default()).collect()),
+            Bool(v)   => Bool(idx.iter().map(|o| o.map(|i| v[i]).unwrap_or(false)).collect()),
+        }
+    }
+
+    /// Like [`Column::gather`], but for key columns that may come from
+    /// either side of a join: take the value from `self` at `left_idx[i]`
+    /// if present, otherwise fall back to `other` at `right_idx[i]`. Used to
+    /// fill in the key columns of rows contributed solely by the right side
+    /// of an `outer_join`. `self` and `other` are assumed to share a variant
+    /// (the caller must have already checked this, as `join_ck` does).
+    fn coalesce(&self, other: &Column, left_idx: &[Option<usize>], right_idx: &[Option<usize>]) -> Self
+    {
+        use self::Column::*;
+        match (self, other)
+        {
+            (Float(a), Float(b)) => Float(left_idx.iter().zip(right_idx)
+                .map(|(l, r)| l.map(|i| a[i]).or_else(|| r.map(|i| b[i])).unwrap_or(f32::NAN))
+                .collect()),
+            (Double(a), Double(b)) => Double(left_idx.iter().zip(right_idx)
+                .map(|(l, r)| l.map(|i| a[i]).or_else(|| r.map(|i| b[i])).unwrap_or(f64::NAN))
+                .collect()),
+            (Factor(a), Factor(b)) => Factor(left_idx.iter().zip(right_idx)
+                .map(|(l, r)| l.map(|i| a[i].clone()).or_else(|| r.map(|i| b[i].clone())).unwrap_or_default())
+                .collect()),
+            (Bool(a), Bool(b)) => Bool(left_idx.iter().zip(right_idx)
+                .map(|(l, r)| l.map(|i| a[i]).or_else(|| r.map(|i| b[i])).unwrap_or(false))
+                .collect()),
+
+            // join_ck already checked that key columns share a variant.
+            _ => panic!("invalid join: mismatched key column types"),
+        }
+    }
+
+    /// Serialize the value at `row` into a string suitable for hashing as a
+    /// join key (floats use their `Debug` form, so equal values hash and
+    /// compare equal).
+    fn cell_key(&self, row: usize) -> String
     {
         use self::Column::*;
         match self
         {
-            &Float(_)  => "Float",
-            &Double(_) => "Double",
-            &Factor(_) => "Factor",
-            &Bool(_)   => "Boolean",
+            Float(v)  => format!("{:?}", v[row]),
+            Double(v) => format!("{:?}", v[row]),
+            Factor(v) => v[row].clone(),
+            Bool(v)   => v[row].to_string(),
+        }
+    }
+
+    /// Remove the rows in `ix..end` in place.
+    fn drop_rows_at(&mut self, ix: usize, end: usize)
+    {
+        use self::Column::*;
+        match self
+        {
+            Float(v)  => { v.drain(ix..end); }
+            Double(v) => { v.drain(ix..end); }
+            Factor(v) => { v.drain(ix..end); }
+            Bool(v)   => { v.drain(ix..end); }
         }
     }
 }
 
 /// A dataframe.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct DataFrame
 {
     columns: HashMap<String, Column>,
@@ -93,6 +177,36 @@ impl DataFrame
         Self::default()
     }
 
+    /// Load a dataframe from a [`DataSource`], e.g. [`Csv`].
+    pub fn load<S: DataSource>(source: S) -> Result<Self>
+    {
+        source.load()
+    }
+
+    /// Number of rows in this dataframe.
+    pub fn nrow(&self) -> usize
+    {
+        self.nrow
+    }
+
+    /// Read the value in column `name` at `row`, widened to a
+    /// [`crate::traits::Value`] (the same value type the `rpn`/`mutate`
+    /// evaluator reads out of a `Record`). Returns `None` if `name` isn't a
+    /// column of this dataframe.
+    ///
+    /// Panics if `row >= self.nrow()`, same as indexing a `Vec` out of
+    /// bounds.
+    pub fn value(&self, name: &str, row: usize) -> Option<Value>
+    {
+        Some(match self.columns.get(name)?
+        {
+            Column::Float(v)  => Value::Float(v[row] as f64),
+            Column::Double(v) => Value::Float(v[row]),
+            Column::Factor(v) => Value::Str(v[row].clone()),
+            Column::Bool(v)   => Value::Bool(v[row]),
+        })
+    }
+
     /// Checks that this dataframe and `other` are compatible for `cbind`.
     fn cbind_ck(&self, other: &DataFrame) -> Result<()>
     {
@@ -105,8 +219,7 @@ impl DataFrame
         {
             if self.columns.contains_key(key)
             {
-                // TODO suggest to user to use `join` instead.
-                return Error::General("Cannot cbind dataframes with conflicting column names.").err();
+                return Error::General("Cannot cbind dataframes with conflicting column names; use `inner_join`/`left_join`/`outer_join` instead.").err();
             }
         }
 
@@ -236,10 +349,10 @@ impl DataFrame
             use self::Column::*;
             match (val, &other.columns[key]) 
             {
-                (Float(ref mut v) , &Float(ref o))  => v.extend(o.iter()),
-                (Double(ref mut v), &Double(ref o)) => v.extend(o.iter()),
-                (Factor(ref mut v), &Factor(ref o)) => v.extend(o.iter().cloned()),
-                (Bool(ref mut v)  , &Bool(ref o))   => v.extend(o.iter()),
+                (Float(ref mut v) , Float(ref o))  => v.extend(o.iter()),
+                (Double(ref mut v), Double(ref o)) => v.extend(o.iter()),
+                (Factor(ref mut v), Factor(ref o)) => v.extend(o.iter().cloned()),
+                (Bool(ref mut v)  , Bool(ref o))   => v.extend(o.iter()),
 
                 // rbind_ck already checked that this is not the case.
                 _ => panic!("invalid rbind"),
@@ -291,22 +404,239 @@ impl DataFrame
         Ok(self)
     }
 
-    /// Create a new dataframe with columns which satisfy the predicate.
+    /// Remove a single column from this dataframe.
+    pub fn drop_column(&mut self, name: &str) -> Result<&mut Self>
+    {
+        if self.columns.remove(name).is_none()
+        {
+            return ErrorString::from("Column `").p(name).p("` is not present in dataframe.").err();
+        }
+
+        Ok(self)
+    }
+
+    /// Remove `n` rows starting at `ix`, updating every column and `nrow` in
+    /// lockstep. If `ix + n` overshoots the end of the dataframe, deletion
+    /// simply runs through the last row instead of erroring (as lace's
+    /// `del_rows_at` does).
+    pub fn drop_rows_at(&mut self, ix: usize, n: usize) -> &mut Self
+    {
+        let ix = ix.min(self.nrow);
+        let end = (ix + n).min(self.nrow);
+
+        for col in self.columns.values_mut()
+        {
+            col.drop_rows_at(ix, end);
+        }
+
+        self.nrow -= end - ix;
+        self
+    }
+
+    /// Create a new dataframe keeping only the rows which satisfy `p`.
+    ///
+    /// `p` is handed a [`DfToken`] "notepad" rather than a real row; indexing
+    /// it (`&d["Foo"]`) and combining the results with `+`/`-`/`*` and
+    /// `<`/`>`/`==` records an expression instead of computing anything.
+    /// Once `p` returns, that expression is typechecked against this
+    /// dataframe's columns and, only if it checks out, evaluated once per
+    /// row (in parallel) to build the keep/drop mask. This is how we get
+    /// `dplyr`-style `df.filter(|d| &d["Foo"] + &d["Bar"] < 3)` with real
+    /// validation instead of panics on nonsense like adding a `Factor` to a
+    /// `Float`.
     pub fn filter<F: FnOnce(DfToken) -> bool>(&self, p: F) -> Result<Self>
     {
-        ErrorString::from("unimplemented").err()
+        let token = DfToken::new();
+        p(token.clone());
+        let tree = token.into_tree()?;
+
+        let mask = expr::filter_mask(&tree, self)?;
+
+        let mut result = Self
+        {
+            nrow: mask.iter().filter(|&&keep| keep).count(),
+            ..Self::default()
+        };
+
+        for (key, col) in self.columns.iter()
+        {
+            result.columns.insert(key.clone(), col.keep(&mask));
+        }
+
+        Ok(result)
     }
-}
 
-impl Default for DataFrame
-{
-    fn default() -> Self
+    /// Checks that `keys` name columns present in both `self` and `other`,
+    /// sharing the same `Column` variant.
+    fn join_ck(&self, other: &DataFrame, keys: &[&str]) -> Result<()>
     {
-        Self
+        for key in keys
         {
-            columns: HashMap::default(),
-            nrow: 0 as usize,
+            match (self.columns.get(*key), other.columns.get(*key))
+            {
+                (None, _) => return ErrorString::from("Key column `").p(key).p("` is not present in self.").err(),
+                (_, None) => return ErrorString::from("Key column `").p(key).p("` is not present in other.").err(),
+
+                (Some(l), Some(r)) =>
+                {
+                    use self::Column::*;
+                    match (l, r)
+                    {
+                        (Float(_) , Float(_))  => continue,
+                        (Double(_), Double(_)) => continue,
+                        (Factor(_), Factor(_)) => continue,
+                        (Bool(_)  , Bool(_))   => continue,
+
+                        _ => return ErrorString::new()
+                            .p("Key column `")
+                            .p(key)
+                            .p("` is a `")
+                            .p(l.variant_str())
+                            .p("` in self, but a `")
+                            .p(r.variant_str())
+                            .p("` in other.")
+                            .err(),
+                    }
+                }
+            }
         }
+
+        Ok(())
+    }
+
+    /// Serialize the values of `keys` at `row` into a hashable join key.
+    fn row_key(&self, keys: &[&str], row: usize) -> Vec<String>
+    {
+        keys.iter().map(|key| self.columns[*key].cell_key(row)).collect()
+    }
+
+    /// Build an index from the serialized key tuple of each row to every row
+    /// (there may be more than one) that produced it.
+    fn build_key_index(&self, keys: &[&str]) -> HashMap<Vec<String>, Vec<usize>>
+    {
+        let mut index: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+
+        for row in 0..self.nrow
+        {
+            index.entry(self.row_key(keys, row)).or_default().push(row);
+        }
+
+        index
+    }
+
+    /// Shared implementation of `inner_join`/`left_join`/`outer_join`: match
+    /// rows of `self` ("left") against `other` ("right") on `keys`, building
+    /// a `HashMap` from the right frame's serialized key tuples to its row
+    /// indices, then walking the left frame's rows to find matches. Rows
+    /// left unmatched on either side are included (with the other side
+    /// null-filled, see [`Column::gather`]) according to `keep_left`/
+    /// `keep_right`.
+    fn join(&self, other: &DataFrame, keys: &[&str], keep_left: bool, keep_right: bool) -> Result<Self>
+    {
+        self.join_ck(other, keys)?;
+
+        let right_index = other.build_key_index(keys);
+
+        let mut left_idx: Vec<Option<usize>> = Vec::new();
+        let mut right_idx: Vec<Option<usize>> = Vec::new();
+        let mut matched_right = vec![false; other.nrow];
+
+        for row in 0..self.nrow
+        {
+            match right_index.get(&self.row_key(keys, row))
+            {
+                Some(matches) => for &r in matches
+                {
+                    left_idx.push(Some(row));
+                    right_idx.push(Some(r));
+                    matched_right[r] = true;
+                },
+
+                None if keep_left =>
+                {
+                    left_idx.push(Some(row));
+                    right_idx.push(None);
+                }
+
+                None => {}
+            }
+        }
+
+        if keep_right
+        {
+            for (row, &matched) in matched_right.iter().enumerate()
+            {
+                if !matched
+                {
+                    left_idx.push(None);
+                    right_idx.push(Some(row));
+                }
+            }
+        }
+
+        let mut result = Self
+        {
+            nrow: left_idx.len(),
+            ..Self::default()
+        };
+
+        for key in keys
+        {
+            result.columns.insert(key.to_string(), self.columns[*key].coalesce(&other.columns[*key], &left_idx, &right_idx));
+        }
+
+        for (name, col) in self.columns.iter().filter(|(name, _)| !keys.contains(&name.as_str()))
+        {
+            let out_name = if other.columns.contains_key(name) && !keys.contains(&name.as_str())
+            {
+                format!("{}_x", name)
+            }
+            else
+            {
+                name.clone()
+            };
+
+            result.columns.insert(out_name, col.gather(&left_idx));
+        }
+
+        for (name, col) in other.columns.iter().filter(|(name, _)| !keys.contains(&name.as_str()))
+        {
+            let out_name = if self.columns.contains_key(name) && !keys.contains(&name.as_str())
+            {
+                format!("{}_y", name)
+            }
+            else
+            {
+                name.clone()
+            };
+
+            result.columns.insert(out_name, col.gather(&right_idx));
+        }
+
+        Ok(result)
+    }
+
+    /// Join `self` and `other` on `keys`, keeping only rows with a match on
+    /// both sides.
+    pub fn inner_join(&self, other: &DataFrame, keys: &[&str]) -> Result<Self>
+    {
+        self.join(other, keys, false, false)
+    }
+
+    /// Join `self` and `other` on `keys`, keeping every row of `self`; rows
+    /// of `other` without a match are dropped, and rows of `self` without a
+    /// match have their `other`-side columns null-filled.
+    pub fn left_join(&self, other: &DataFrame, keys: &[&str]) -> Result<Self>
+    {
+        self.join(other, keys, true, false)
+    }
+
+    /// Join `self` and `other` on `keys`, keeping every row of both sides;
+    /// whichever side didn't contribute to a row has its columns
+    /// null-filled.
+    pub fn outer_join(&self, other: &DataFrame, keys: &[&str]) -> Result<Self>
+    {
+        self.join(other, keys, true, true)
     }
 }
 
@@ -314,6 +644,7 @@ pub mod error_string
 {
     use super::*;
 
+    #[derive(Default)]
     pub struct ErrorString
     {
         s: String
@@ -323,16 +654,13 @@ pub mod error_string
     {
         pub fn new() -> Self
         {
-            Self
-            {
-                s: String::new(),
-            }
+            Self::default()
         }
 
         /// `p`, short for `paste`. Extends string with the argument.
         pub fn p(mut self, ss: &str) -> Self
         {
-            self.s.extend(ss.chars());
+            self.s.push_str(ss);
             self
         }
 
@@ -343,22 +671,11 @@ pub mod error_string
         }
     }
 
-    impl Default for ErrorString
-    {
-        fn default() -> Self
-        {
-            Self
-            {
-                s: String::default(),
-            }
-        }
-    }
-
-    impl Into<String> for ErrorString
+    impl From<ErrorString> for String
     {
-        fn into(self) -> String
+        fn from(val: ErrorString) -> Self
         {
-            self.s
+            val.s
         }
     }
 
@@ -384,11 +701,11 @@ pub mod error_string
         }
     }
 
-    impl Into<Error> for ErrorString
+    impl From<ErrorString> for Error
     {
-        fn into(self) -> Error
+        fn from(val: ErrorString) -> Self
         {
-            Error::GeneralBuf(self.s)
+            Error::GeneralBuf(val.s)
         }
     }
 }
@@ -0,0 +1,93 @@
+//! Loading a `DataFrame` from an external source.
+//!
+//! Mirrors lace's `Engine`, which takes a `data_source` and `load`s it.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{Column, DataFrame, Error, Result};
+
+/// Something that can be loaded into a [`DataFrame`].
+pub trait DataSource
+{
+    fn load(self) -> Result<DataFrame>;
+}
+
+/// Load a dataframe from a CSV file, inferring each column's [`Column`]
+/// variant from its values: all-parseable-as-bool becomes `Bool`, all
+/// integral/float becomes `Float` or `Double`, and everything else becomes
+/// `Factor`.
+pub struct Csv<P: AsRef<Path>>
+{
+    path: P,
+}
+
+impl<P: AsRef<Path>> Csv<P>
+{
+    pub fn new(path: P) -> Self
+    {
+        Self { path }
+    }
+}
+
+impl<P: AsRef<Path>> DataSource for Csv<P>
+{
+    fn load(self) -> Result<DataFrame>
+    {
+        let mut reader = csv::Reader::from_path(self.path)
+            .map_err(|e| Error::GeneralBuf(format!("Failed to open CSV: {}", e)))?;
+
+        let headers: Vec<String> = reader.headers()
+            .map_err(|e| Error::GeneralBuf(format!("Failed to read CSV headers: {}", e)))?
+            .iter()
+            .map(String::from)
+            .collect();
+
+        let mut raw: Vec<Vec<String>> = vec![Vec::new(); headers.len()];
+
+        for result in reader.records()
+        {
+            let record = result.map_err(|e| Error::GeneralBuf(format!("Failed to read CSV row: {}", e)))?;
+
+            for (i, field) in record.iter().enumerate()
+            {
+                raw[i].push(field.to_string());
+            }
+        }
+
+        let nrow = raw.first().map_or(0, Vec::len);
+        let columns: HashMap<String, Column> = headers.into_iter()
+            .zip(raw)
+            .map(|(name, values)| (name, infer_column(&values)))
+            .collect();
+
+        Ok(DataFrame { columns, nrow })
+    }
+}
+
+/// Infer the `Column` variant that best fits `values`, trying `Bool` first,
+/// then a numeric variant, and falling back to `Factor`.
+fn infer_column(values: &[String]) -> Column
+{
+    if values.iter().all(|v| v.trim().parse::<bool>().is_ok())
+    {
+        return Column::Bool(values.iter().map(|v| v.trim().parse().unwrap()).collect());
+    }
+
+    if let Some(doubles) = values.iter().map(|v| v.trim().parse::<f64>().ok()).collect::<Option<Vec<f64>>>()
+    {
+        // Use the narrower `Float` when every value round-trips through f32
+        // without losing precision, same as reading a column of "1.5"s
+        // shouldn't force the whole column into f64.
+        return if doubles.iter().all(|&d| f64::from(d as f32) == d)
+        {
+            Column::Float(doubles.into_iter().map(|d| d as f32).collect())
+        }
+        else
+        {
+            Column::Double(doubles)
+        };
+    }
+
+    Column::Factor(values.to_vec())
+}
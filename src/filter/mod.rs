@@ -4,6 +4,7 @@ use rayon::prelude::*;
 
 use crate::traits::*;
 use crate::dataframe::DataFrame;
+use crate::rpn::{self, Df};
 
 impl<R: Record> DataFrame<R>
 {
@@ -18,4 +19,31 @@ impl<R: Record> DataFrame<R>
                 .collect()
         }
     }
+
+    /// Return the subset of rows that satisfy an `rpn` expression.
+    ///
+    /// `expr` is handed a [`Df`] "notepad"; whatever the closure writes down
+    /// on it (e.g. `|d| &d["foo"] > 3.0`) is recorded as a program and run
+    /// against every row. A row is kept if its program evaluates to `true`.
+    /// Errors if the program doesn't reduce to a boolean for some row.
+    pub fn filter_expr<F: FnOnce(Df) -> bool>(&self, expr: F) -> rpn::Result<DataFrame<R>>
+    {
+        let df = Df::new();
+        expr(df.clone());
+        let program = df.into_program();
+
+        let rows = self.rows
+            .par_iter()
+            .cloned()
+            .filter_map(|r| match rpn::run(&program, &r)
+            {
+                Ok(Value::Bool(true))  => Some(Ok(r)),
+                Ok(Value::Bool(false)) => None,
+                Ok(_)                  => Some(Err(rpn::Error::NotBoolean)),
+                Err(e)                 => Some(Err(e)),
+            })
+            .collect::<rpn::Result<Vec<R>>>()?;
+
+        Ok(DataFrame { rows })
+    }
 }
@@ -4,6 +4,7 @@ use crate::traits::*;
 use crate::dataframe::DataFrame;
 
 /// Populates the required argument for `gather`.
+#[macro_export]
 macro_rules! gather
 {
     ($key:ident, $val:ident, $( $col:ident ),*) =>
@@ -0,0 +1,23 @@
+//! `to_json`/`from_json`
+
+use crate::traits::*;
+use crate::dataframe::DataFrame;
+
+impl<R: Record + serde::Serialize> DataFrame<R>
+{
+    /// Serialize this dataframe to a JSON string, as the sequence of its
+    /// records.
+    pub fn to_json(&self) -> serde_json::Result<String>
+    {
+        serde_json::to_string(self)
+    }
+}
+
+impl<R: Record + serde::de::DeserializeOwned> DataFrame<R>
+{
+    /// Parse a dataframe from a JSON array of records.
+    pub fn from_json(s: &str) -> serde_json::Result<Self>
+    {
+        serde_json::from_str(s)
+    }
+}
@@ -2,22 +2,24 @@ extern crate csv;
 extern crate serde;
 extern crate prettytable;
 extern crate rayon;
-
-#[macro_use]
-extern crate serde_derive;
-
-use std::iter::Extend;
+extern crate rand;
+extern crate rand_xoshiro;
+extern crate serde_json;
 
 pub mod dataframe;
 pub use dataframe::DataFrame;
-pub use dataframe::traits::*;
 
 pub mod select;
 pub mod mutate;
 pub mod filter;
 pub mod gather;
+pub mod sample;
+pub mod json;
 // pub mod spread;
 
+pub mod dynamic;
+pub mod rpn;
+
 /// Here, we define some pseudo-trait aliases just to make things
 /// a little easier.
 pub mod traits
@@ -26,9 +28,75 @@ pub mod traits
     pub trait ThreadSafe: Send + Sync {}
     impl<T: Send + Sync> ThreadSafe for T {}
 
+    /// A value read out of a [`Record`] field by name.
+    ///
+    /// Used by the `rpn` module's stack machine, which has no way to know a
+    /// `Record`'s concrete field types at compile time.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Value
+    {
+        Float(f64),
+        Bool(bool),
+        Str(String),
+    }
+
     /// This trait is used as a bound on the type being stored in the dataframe.
-    pub trait Record: Clone + ThreadSafe {}
-    impl<T: Clone + ThreadSafe> Record for T {}
+    ///
+    /// `get`/`set` let the `rpn` module's evaluator read and write a named
+    /// field at runtime. There's no default implementation (and so no more
+    /// blanket impl below) since there's no type-agnostic way to enumerate a
+    /// struct's fields; every `Record` names its own fields explicitly.
+    pub trait Record: Clone + ThreadSafe
+    {
+        /// Look up the value of the field named `name`, or `None` if this
+        /// record has no such field.
+        fn get(&self, name: &'static str) -> Option<Value>;
+
+        /// Overwrite the field named `name` with `value`. Does nothing if
+        /// this record has no such field.
+        fn set(&mut self, name: &'static str, value: Value);
+    }
+
+    /// A `Record` made of two other `Record`s, so that `lookup`'s predicate
+    /// and transform (which both operate on a pair of rows, one from each
+    /// side of the lookup) can be bound by [`Predicate`]/[`Transform`] like
+    /// any other record.
+    ///
+    /// `get` checks the left side first, then the right; `set` writes
+    /// through to whichever side has the field.
+    impl<A: Record, B: Record> Record for (A, B)
+    {
+        fn get(&self, name: &'static str) -> Option<Value>
+        {
+            self.0.get(name).or_else(|| self.1.get(name))
+        }
+
+        fn set(&mut self, name: &'static str, value: Value)
+        {
+            if self.0.get(name).is_some()
+            {
+                self.0.set(name, value);
+            }
+            else
+            {
+                self.1.set(name, value);
+            }
+        }
+    }
+
+    /// A shared reference to a `Record` is itself a `Record`, so that
+    /// `lookup`'s predicate (which runs on borrowed rows) can be bound by
+    /// [`Predicate`] the same way as everything else. There's no way to
+    /// write through a shared reference, so `set` is a no-op.
+    impl<A: Record + Sync> Record for &A
+    {
+        fn get(&self, name: &'static str) -> Option<Value>
+        {
+            (**self).get(name)
+        }
+
+        fn set(&mut self, _name: &'static str, _value: Value) {}
+    }
 
     /// Represents a function that transforms records from one type into another.
     pub trait Transform<R: Record, N: Record>: ThreadSafe + Fn(R) -> N {}
@@ -50,41 +118,134 @@ pub mod traits
 #[cfg(test)]
 mod test
 {
-    #[test]
-    fn test()
+    #[allow(dead_code)]
+    #[derive(Debug, Clone)]
+    struct Record
     {
-        #[derive(Debug, Clone)]
-        struct Record
+        id: usize,
+        foo: f32,
+        name: String,
+    }
+
+    impl super::traits::Record for Record
+    {
+        fn get(&self, name: &'static str) -> Option<super::traits::Value>
+        {
+            use super::traits::Value;
+            match name
+            {
+                "id"   => Some(Value::Float(self.id as f64)),
+                "foo"  => Some(Value::Float(self.foo as f64)),
+                "name" => Some(Value::Str(self.name.clone())),
+                _ => None,
+            }
+        }
+
+        fn set(&mut self, name: &'static str, value: super::traits::Value)
         {
-            id: usize,
-            foo: f32,
-            name: String,
+            use super::traits::Value;
+            match (name, value)
+            {
+                ("id", Value::Float(v))   => self.id = v as usize,
+                ("foo", Value::Float(v))  => self.foo = v as f32,
+                ("name", Value::Str(v))   => self.name = v,
+                _ => {}
+            }
         }
+    }
 
-        type DataFrame = super::dataframe::DataFrame<Record>;
+    type DataFrame = super::dataframe::DataFrame<Record>;
 
+    fn people() -> DataFrame
+    {
         let mut df = DataFrame::new();
         df.extend([
-            Record { id: 32 as usize, foo: 3.43, name: "name".to_string() },
-            Record { id: 1  as usize, foo: 6.54, name: "nrme".to_string() },
-            Record { id: 2  as usize, foo: 9.66, name: "nlme".to_string() },
-            Record { id: 3  as usize, foo: 0.25, name: "nfme".to_string() },
-            Record { id: 4  as usize, foo: 2.29, name: "naoe".to_string() },
-            Record { id: 5  as usize, foo: 1.74, name: "nase".to_string() },
-            Record { id: 6  as usize, foo: 5.49, name: "name".to_string() },
-            Record { id: 7  as usize, foo: 6.30, name: "naye".to_string() },
-            Record { id: 8  as usize, foo: 7.72, name: "nace".to_string() },
-            Record { id: 11 as usize, foo: 8.81, name: "name".to_string() },
-            Record { id: 21 as usize, foo: 9.96, name: "nvme".to_string() }
-        ].into_iter().cloned());
+            Record { id: 32, foo: 3.43, name: "name".to_string() },
+            Record { id: 1,  foo: 6.54, name: "nrme".to_string() },
+            Record { id: 2,  foo: 9.66, name: "nlme".to_string() },
+            Record { id: 3,  foo: 0.25, name: "nfme".to_string() },
+            Record { id: 4,  foo: 2.29, name: "naoe".to_string() },
+            Record { id: 5,  foo: 1.74, name: "nase".to_string() },
+            Record { id: 6,  foo: 5.49, name: "name".to_string() },
+            Record { id: 7,  foo: 6.30, name: "naye".to_string() },
+            Record { id: 8,  foo: 7.72, name: "nace".to_string() },
+            Record { id: 11, foo: 8.81, name: "name".to_string() },
+            Record { id: 21, foo: 9.96, name: "nvme".to_string() }
+        ].iter().cloned());
+        df
+    }
+
+    #[test]
+    fn test()
+    {
+        let df = people();
 
         println!("{:?}", df);
 
-        println!("{:?}", df.mutate(|r: &mut Record| r.foo = 2.0*r.foo));
+        println!("{:?}", df.mutate(|r: &mut Record| r.foo *= 2.0));
         println!("{:?}", df.filter(|r: &Record| r.name == "name"));
         println!("{:?}", df.filter(|r: &Record| r.foo > 3.0));
     }
 
+    #[test]
+    fn filter_expr_keeps_matching_rows()
+    {
+        let df = people();
+
+        // foo > 3.0 drops ids 3, 4, 5 (foo = 0.25, 2.29, 1.74) and keeps
+        // the other 8.
+        let kept = df.filter_expr(|d| &d["foo"] > 3.0).unwrap();
+        assert_eq!(kept.len(), 8);
+        assert!(kept.rows.iter().all(|r| r.foo > 3.0));
+        assert!(!kept.rows.iter().any(|r| [3, 4, 5].contains(&r.id)));
+    }
+
+    #[test]
+    fn mutate_expr_writes_computed_values()
+    {
+        let df = people();
+
+        let mutated = df.mutate_expr("foo", |d| &d["foo"] + &d["id"]).unwrap();
+        for (before, after) in df.rows.iter().zip(mutated.rows.iter())
+        {
+            assert!((after.foo - (before.foo + before.id as f32)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn rpn_error_variants()
+    {
+        use super::rpn::{self, Item};
+
+        let record = Record { id: 1, foo: 2.0, name: "x".to_string() };
+
+        // stack underflow: an operator with no preceding operands.
+        assert!(matches!(rpn::run(&[Item::Add], &record), Err(rpn::Error::StackUnderflow)));
+
+        // too many results: the program doesn't reduce to exactly one value.
+        assert!(matches!(
+            rpn::run(&[Item::Const(1.0), Item::Const(2.0)], &record),
+            Err(rpn::Error::TooManyResults)
+        ));
+
+        // unknown field.
+        assert!(matches!(
+            rpn::run(&[Item::OwnColumn("nonexistent")], &record),
+            Err(rpn::Error::UnknownField("nonexistent"))
+        ));
+
+        // type mismatch: comparing a Str field against a Float literal.
+        assert!(matches!(
+            rpn::run(&[Item::OwnColumn("name"), Item::Const(1.0), Item::Gt], &record),
+            Err(rpn::Error::TypeMismatch)
+        ));
+
+        // filter_expr's own `NotBoolean` wrapping: a program that evaluates
+        // to something other than a boolean.
+        let df = people();
+        assert!(matches!(df.filter_expr(|d| { let _ = &d["foo"]; true }), Err(rpn::Error::NotBoolean)));
+    }
+
     // #[test]
     // fn test_gather()
     // {
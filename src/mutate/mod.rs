@@ -4,6 +4,7 @@ use rayon::prelude::*;
 
 use crate::traits::*;
 use crate::dataframe::DataFrame;
+use crate::rpn::{self, Df};
 
 impl<R: Record> DataFrame<R>
 {
@@ -12,4 +13,29 @@ impl<R: Record> DataFrame<R>
     {
         self.transform(|mut r| { mutation(&mut r); r })
     }
+
+    /// Create or alter `target` using an `rpn` expression.
+    ///
+    /// `expr` is handed a [`Df`] "notepad"; whatever the closure writes down
+    /// on it (e.g. `|d| &d["foo"] + &d["bar"]`) is recorded as a program and
+    /// run against every row, writing the result into `target`.
+    pub fn mutate_expr<F: FnOnce(Df) -> Df>(&self, target: &'static str, expr: F) -> rpn::Result<DataFrame<R>>
+    {
+        let df = Df::new();
+        expr(df.clone());
+        let program = df.into_program();
+
+        let rows = self.rows
+            .par_iter()
+            .cloned()
+            .map(|mut r|
+            {
+                let value = rpn::run(&program, &r)?;
+                r.set(target, value);
+                Ok(r)
+            })
+            .collect::<rpn::Result<Vec<R>>>()?;
+
+        Ok(DataFrame { rows })
+    }
 }
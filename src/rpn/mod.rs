@@ -0,0 +1,262 @@
+//! RPN evaluation engine backing `DataFrame::filter_expr`/`mutate_expr`.
+//!
+//! This is the generic-`DataFrame<R>` counterpart to the "notepad" idea
+//! sketched in `df_token.rs` and finished (as a typecheck-then-evaluate tree)
+//! by `dynamic::expr`'s `DfToken`. Here there's no separate typecheck pass:
+//! the user's closure is handed a [`Df`], and indexing/operators record a
+//! flat reverse-polish program (the same `Vec<Item>` stack layout as the
+//! prototype) instead of a tree. [`run`] then executes that program directly
+//! against a row, one row at a time, as a stack machine - a malformed or
+//! ill-typed program (stack underflow, leftover operands, a non-boolean
+//! filter result) is reported as an [`Error`] the moment `run` hits it,
+//! rather than being caught ahead of time.
+
+use std::cell::RefCell;
+use std::ops::{Add, Div, Mul, Sub};
+use std::rc::Rc;
+
+use crate::traits::{Record, Value};
+
+/// One entry in the flat RPN program recorded by a [`Df`].
+#[derive(Debug, Clone)]
+pub(crate) enum Item
+{
+    OwnColumn(&'static str),
+    Const(f64),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Gt,
+    Eq,
+}
+
+/// Errors raised while running an RPN program against a record.
+#[derive(Debug)]
+pub enum Error
+{
+    /// The program referenced a field that isn't on this record.
+    UnknownField(&'static str),
+
+    /// The program popped an operand off an empty stack.
+    StackUnderflow,
+
+    /// The program left more than one value on the stack once it finished.
+    TooManyResults,
+
+    /// An operator was applied to operands it doesn't support (e.g. adding a
+    /// string to a number).
+    TypeMismatch,
+
+    /// `filter_expr`'s program finished with something other than a `Bool`.
+    NotBoolean,
+}
+
+/// Result type.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// A value of this type is passed into the closure given to
+/// `DataFrame::filter_expr`/`mutate_expr`.
+///
+/// Indexing (`&df["foo"]`) and the arithmetic/comparison operators below
+/// don't do any real work; they just push onto a shared RPN stack. Every
+/// `Df` derived from the same root (via `Clone`, which is cheap - it only
+/// bumps an `Rc`) shares that stack, so the whole expression recorded during
+/// one call to the closure ends up in one program, ready for
+/// [`Df::into_program`] to hand to [`run`].
+#[derive(Clone)]
+pub struct Df
+{
+    stack: Rc<RefCell<Vec<Item>>>,
+}
+
+impl Df
+{
+    pub(crate) fn new() -> Self
+    {
+        Self { stack: Rc::new(RefCell::new(Vec::new())) }
+    }
+
+    fn push(&self, item: Item) -> Self
+    {
+        self.stack.borrow_mut().push(item);
+        self.clone()
+    }
+
+    /// Consume the token, returning the flat program it recorded.
+    pub(crate) fn into_program(self) -> Vec<Item>
+    {
+        match Rc::try_unwrap(self.stack)
+        {
+            Ok(cell) => cell.into_inner(),
+            Err(shared) => shared.borrow().clone(),
+        }
+    }
+}
+
+/// Indexed with a `&'static str` (rather than a generic `&'a str`, as
+/// `DfToken` allows) since `Record::get`/`set` key fields by `&'static str`.
+/// In practice this just means indexing with a string literal, e.g.
+/// `&df["foo"]`.
+impl ::std::ops::Index<&'static str> for Df
+{
+    type Output = Self;
+    fn index(&self, index: &'static str) -> &Self::Output
+    {
+        self.push(Item::OwnColumn(index));
+        self
+    }
+}
+
+impl<'a> Add<&'a Df> for &Df
+{
+    type Output = Df;
+    fn add(self, _rhs: &'a Df) -> Df { self.push(Item::Add) }
+}
+
+impl<'a> Sub<&'a Df> for &Df
+{
+    type Output = Df;
+    fn sub(self, _rhs: &'a Df) -> Df { self.push(Item::Sub) }
+}
+
+impl<'a> Mul<&'a Df> for &Df
+{
+    type Output = Df;
+    fn mul(self, _rhs: &'a Df) -> Df { self.push(Item::Mul) }
+}
+
+impl<'a> Div<&'a Df> for &Df
+{
+    type Output = Df;
+    fn div(self, _rhs: &'a Df) -> Df { self.push(Item::Div) }
+}
+
+impl PartialEq<f64> for &Df
+{
+    fn eq(&self, other: &f64) -> bool
+    {
+        self.push(Item::Const(*other));
+        self.push(Item::Eq);
+        true
+    }
+}
+
+impl<'a> PartialEq<&'a Df> for &Df
+{
+    fn eq(&self, _other: &&'a Df) -> bool
+    {
+        self.push(Item::Eq);
+        true
+    }
+}
+
+impl PartialOrd<f64> for &Df
+{
+    fn partial_cmp(&self, _other: &f64) -> Option<::std::cmp::Ordering> { None }
+
+    fn lt(&self, other: &f64) -> bool
+    {
+        self.push(Item::Const(*other));
+        self.push(Item::Lt);
+        true
+    }
+
+    fn gt(&self, other: &f64) -> bool
+    {
+        self.push(Item::Const(*other));
+        self.push(Item::Gt);
+        true
+    }
+}
+
+impl<'a> PartialOrd<&'a Df> for &Df
+{
+    fn partial_cmp(&self, _other: &&'a Df) -> Option<::std::cmp::Ordering> { None }
+
+    fn lt(&self, _other: &&'a Df) -> bool
+    {
+        self.push(Item::Lt);
+        true
+    }
+
+    fn gt(&self, _other: &&'a Df) -> bool
+    {
+        self.push(Item::Gt);
+        true
+    }
+}
+
+/// Run an RPN program against a single record, returning the value left on
+/// the stack once the program is exhausted.
+///
+/// Errors (rather than panics) on stack underflow, a type mismatch between
+/// an operator and its operands, or a program that doesn't reduce to exactly
+/// one value.
+pub(crate) fn run<R: Record>(program: &[Item], record: &R) -> Result<Value>
+{
+    let mut stack: Vec<Value> = Vec::new();
+
+    for item in program
+    {
+        match item
+        {
+            Item::OwnColumn(name) =>
+            {
+                stack.push(record.get(name).ok_or(Error::UnknownField(name))?);
+            }
+
+            Item::Const(c) => stack.push(Value::Float(*c)),
+
+            Item::Add | Item::Sub | Item::Mul | Item::Div =>
+            {
+                let rhs = stack.pop().ok_or(Error::StackUnderflow)?;
+                let lhs = stack.pop().ok_or(Error::StackUnderflow)?;
+                let (a, b) = match (lhs, rhs)
+                {
+                    (Value::Float(a), Value::Float(b)) => (a, b),
+                    _ => return Err(Error::TypeMismatch),
+                };
+
+                stack.push(Value::Float(match item
+                {
+                    Item::Add => a + b,
+                    Item::Sub => a - b,
+                    Item::Mul => a * b,
+                    Item::Div => a / b,
+                    _ => unreachable!(),
+                }));
+            }
+
+            Item::Lt | Item::Gt | Item::Eq =>
+            {
+                let rhs = stack.pop().ok_or(Error::StackUnderflow)?;
+                let lhs = stack.pop().ok_or(Error::StackUnderflow)?;
+                let ord = match (&lhs, &rhs)
+                {
+                    (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+                    (Value::Str(a), Value::Str(b)) => a.partial_cmp(b),
+                    (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(b),
+                    _ => return Err(Error::TypeMismatch),
+                };
+                let ord = ord.ok_or(Error::TypeMismatch)?;
+
+                stack.push(Value::Bool(match item
+                {
+                    Item::Lt => ord == ::std::cmp::Ordering::Less,
+                    Item::Gt => ord == ::std::cmp::Ordering::Greater,
+                    Item::Eq => ord == ::std::cmp::Ordering::Equal,
+                    _ => unreachable!(),
+                }));
+            }
+        }
+    }
+
+    match (stack.pop(), stack.is_empty())
+    {
+        (Some(v), true) => Ok(v),
+        (None, _) => Err(Error::StackUnderflow),
+        (Some(_), false) => Err(Error::TooManyResults),
+    }
+}
@@ -0,0 +1,85 @@
+//! `sample`/`bootstrap`
+
+use rand::seq::index;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256Plus;
+use rayon::prelude::*;
+
+use crate::traits::*;
+use crate::dataframe::DataFrame;
+
+/// Errors raised by sampling.
+#[derive(Debug)]
+pub enum Error
+{
+    /// General error.
+    General(&'static str),
+}
+
+/// Result type.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl<R: Record> DataFrame<R>
+{
+    /// Sample `n` rows without replacement.
+    ///
+    /// `seed` is used to construct a `Xoshiro256Plus`, so the same seed
+    /// always produces the same sample, regardless of thread scheduling:
+    /// the sampled indices are generated serially from the seeded RNG, and
+    /// only the subsequent row-cloning is parallelized with rayon.
+    ///
+    /// Errors if `n` is greater than the number of rows available - there's
+    /// no way to draw that many rows without replacement.
+    pub fn sample_n(&self, n: usize, seed: u64) -> Result<Self>
+    {
+        if n > self.rows.len()
+        {
+            return Err(Error::General("cannot sample more rows than the dataframe has without replacement."));
+        }
+
+        let mut rng = Xoshiro256Plus::seed_from_u64(seed);
+        let idx = index::sample(&mut rng, self.rows.len(), n).into_vec();
+
+        Ok(DataFrame
+        {
+            rows: idx.into_par_iter().map(|i| self.rows[i].clone()).collect()
+        })
+    }
+
+    /// Sample a fraction `frac` of rows (rounded to the nearest whole row)
+    /// without replacement. See [`DataFrame::sample_n`] for determinism and
+    /// error conditions.
+    ///
+    /// Errors if `frac` is outside `0.0..=1.0`.
+    pub fn sample_frac(&self, frac: f64, seed: u64) -> Result<Self>
+    {
+        if !(0.0..=1.0).contains(&frac)
+        {
+            return Err(Error::General("sample fraction must be between 0.0 and 1.0."));
+        }
+
+        let n = (self.rows.len() as f64 * frac).round() as usize;
+        self.sample_n(n, seed)
+    }
+
+    /// Sample `n` rows *with* replacement. See [`DataFrame::sample_n`] for
+    /// determinism.
+    ///
+    /// Errors if the dataframe is empty - there is nothing to draw from.
+    pub fn bootstrap(&self, n: usize, seed: u64) -> Result<Self>
+    {
+        if self.rows.is_empty()
+        {
+            return Err(Error::General("cannot bootstrap from an empty dataframe."));
+        }
+
+        let mut rng = Xoshiro256Plus::seed_from_u64(seed);
+        let idx: Vec<usize> = (0..n).map(|_| rng.gen_range(0..self.rows.len())).collect();
+
+        Ok(DataFrame
+        {
+            rows: idx.into_par_iter().map(|i| self.rows[i].clone()).collect()
+        })
+    }
+}
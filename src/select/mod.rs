@@ -1,10 +1,9 @@
 //! `select`
 
-use rayon::prelude::*;
-
 use crate::traits::*;
 use crate::dataframe::DataFrame;
 
+#[macro_export]
 macro_rules! select
 {
     ( $typename:ident, $($item:ident),* ) =>
@@ -7,6 +7,7 @@ extern crate csv;
 
 #[macro_use] extern crate serde_derive;
 
+#[allow(dead_code)]
 #[derive(Clone, Debug, Deserialize)]
 struct Record
 {
@@ -17,6 +18,37 @@ struct Record
     class        : String,
 }
 
+impl df_rs::traits::Record for Record
+{
+    fn get(&self, name: &'static str) -> Option<df_rs::traits::Value>
+    {
+        use df_rs::traits::Value;
+        match name
+        {
+            "sepal_length" => Some(Value::Float(self.sepal_length as f64)),
+            "sepal_width"  => Some(Value::Float(self.sepal_width as f64)),
+            "petal_length" => Some(Value::Float(self.petal_length as f64)),
+            "petal_width"  => Some(Value::Float(self.petal_width as f64)),
+            "class"        => Some(Value::Str(self.class.clone())),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, name: &'static str, value: df_rs::traits::Value)
+    {
+        use df_rs::traits::Value;
+        match (name, value)
+        {
+            ("sepal_length", Value::Float(v)) => self.sepal_length = v as f32,
+            ("sepal_width", Value::Float(v))  => self.sepal_width = v as f32,
+            ("petal_length", Value::Float(v)) => self.petal_length = v as f32,
+            ("petal_width", Value::Float(v))  => self.petal_width = v as f32,
+            ("class", Value::Str(v))          => self.class = v,
+            _ => {}
+        }
+    }
+}
+
 type DataFrame = df_rs::DataFrame<Record>;
 
 #[test]
@@ -0,0 +1,252 @@
+//! Integration tests for the `dynamic` (runtime-typed) `DataFrame`.
+
+extern crate df_rs;
+
+use df_rs::dynamic::{Csv, DataFrame, Reducer};
+
+fn people() -> DataFrame
+{
+    DataFrame::load(Csv::new("tests/dynamic.csv")).unwrap()
+}
+
+fn groups() -> DataFrame
+{
+    DataFrame::load(Csv::new("tests/dynamic_join.csv")).unwrap()
+}
+
+fn groups_clash() -> DataFrame
+{
+    DataFrame::load(Csv::new("tests/dynamic_join_clash.csv")).unwrap()
+}
+
+#[test]
+fn load()
+{
+    // the iris fixture exercises a mixed Double/Factor load; `dynamic.csv`
+    // exercises Float/Bool/Factor inference.
+    assert!(DataFrame::load(Csv::new("tests/iris.csv")).is_ok());
+    assert!(DataFrame::load(Csv::new("tests/dynamic.csv")).is_ok());
+
+    assert!(DataFrame::load(Csv::new("tests/no_such_file.csv")).is_err());
+}
+
+#[test]
+fn select()
+{
+    let df = people();
+    assert!(df.select(&["id", "group"]).is_ok());
+    assert!(df.select(&["no_such_column"]).is_err());
+}
+
+#[test]
+fn select_mut()
+{
+    let mut df = people();
+    assert!(df.select_mut(&["id"]).is_ok());
+    assert!(df.select_mut(&["no_such_column"]).is_err());
+}
+
+#[test]
+fn drop_column()
+{
+    let mut df = people();
+    assert!(df.drop_column("flag").is_ok());
+    // dropping it again is an error - it's already gone.
+    assert!(df.drop_column("flag").is_err());
+}
+
+#[test]
+fn drop_rows_at()
+{
+    let mut df = people();
+    df.drop_rows_at(0, 2);
+    // overshooting the end of the dataframe runs through the last row
+    // rather than panicking.
+    df.drop_rows_at(0, 1_000);
+}
+
+#[test]
+fn cbind()
+{
+    let df = people();
+    let left = df.select(&["id", "value"]).unwrap();
+    let right = df.select(&["flag", "group"]).unwrap();
+
+    assert!(left.cbind(&right).is_ok());
+    // conflicting column names aren't allowed.
+    assert!(left.cbind(&df).is_err());
+}
+
+#[test]
+fn cbind_mut()
+{
+    let df = people();
+    let mut left = df.select(&["id", "value"]).unwrap();
+    let right = df.select(&["flag", "group"]).unwrap();
+
+    assert!(left.cbind_mut(&right).is_ok());
+}
+
+#[test]
+fn rbind()
+{
+    let df = people();
+    assert!(df.rbind(&df).is_ok());
+
+    // mismatched columns aren't allowed.
+    let partial = df.select(&["id"]).unwrap();
+    assert!(df.rbind(&partial).is_err());
+}
+
+#[test]
+fn rbind_mut()
+{
+    let df = people();
+    let mut combined = df.clone();
+    assert!(combined.rbind_mut(&df).is_ok());
+}
+
+#[test]
+fn filter()
+{
+    let df = people();
+
+    // a plain column comparison against a Float column.
+    let high_value = df.filter(|d| &d["value"] > 15.0).unwrap();
+    assert_eq!(high_value.nrow(), 3);
+
+    // a combined arithmetic expression compared against a literal - the
+    // canonical example chunk0-1's operator-overload fix exists for.
+    //
+    // id + value per row: 1.5+10=11.5 (dropped), 2.5+20=22.5, 3.5+30=33.5,
+    // 4.5+40=44.5 (all three kept).
+    let combined = df.filter(|d| &d["id"] + &d["value"] > 15.0).unwrap();
+    assert_eq!(combined.nrow(), 3);
+
+    let ids: Vec<f64> = (0..combined.nrow())
+        .map(|row| match combined.value("id", row).unwrap()
+        {
+            df_rs::traits::Value::Float(v) => v,
+            other => panic!("expected a Float, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(ids, vec![2.5, 3.5, 4.5]);
+
+    // filtering on an unknown column is an error, not a panic.
+    assert!(df.filter(|d| &d["no_such_column"] > 1.0).is_err());
+
+    // comparing mismatched types is an error.
+    assert!(df.filter(|d| &d["group"] > 1.0).is_err());
+}
+
+fn str_value(df: &DataFrame, name: &str, row: usize) -> String
+{
+    match df.value(name, row).unwrap()
+    {
+        df_rs::traits::Value::Str(s) => s,
+        other => panic!("expected a Str, got {:?}", other),
+    }
+}
+
+fn float_value(df: &DataFrame, name: &str, row: usize) -> f64
+{
+    match df.value(name, row).unwrap()
+    {
+        df_rs::traits::Value::Float(v) => v,
+        other => panic!("expected a Float, got {:?}", other),
+    }
+}
+
+#[test]
+fn inner_join()
+{
+    let df = people();
+    let g = groups();
+
+    // "c" (people) and "d" (groups) have no match on the other side, so
+    // only the 3 rows whose group is "a" or "b" survive.
+    let joined = df.inner_join(&g, &["group"]).unwrap();
+    assert_eq!(joined.nrow(), 3);
+
+    assert!(df.inner_join(&g, &["no_such_column"]).is_err());
+}
+
+#[test]
+fn left_join()
+{
+    let df = people();
+    let g = groups();
+
+    // every row of `df` is kept; "c" has no match in `groups`, so its
+    // `label` is null-filled with the empty-string placeholder.
+    let joined = df.left_join(&g, &["group"]).unwrap();
+    assert_eq!(joined.nrow(), 4);
+
+    let c_row = (0..joined.nrow()).find(|&row| str_value(&joined, "group", row) == "c").unwrap();
+    assert_eq!(str_value(&joined, "label", c_row), "");
+}
+
+#[test]
+fn inner_join_name_clash()
+{
+    let df = people();
+    let g = groups_clash();
+
+    // both `df` and `groups_clash` have a non-key `value` column; it
+    // should come back as `value_x` (self) / `value_y` (other) rather
+    // than one silently overwriting the other.
+    let joined = df.inner_join(&g, &["group"]).unwrap();
+    assert!(joined.value("value_x", 0).is_some());
+    assert!(joined.value("value_y", 0).is_some());
+    assert!(joined.value("value", 0).is_none());
+}
+
+#[test]
+fn outer_join()
+{
+    let df = people();
+    let g = groups();
+
+    // 3 matched rows (a, a, b) + 1 left-only ("c", no match in `groups`)
+    // + 1 right-only ("d", no match in `df`).
+    let joined = df.outer_join(&g, &["group"]).unwrap();
+    assert_eq!(joined.nrow(), 5);
+
+    // the right-only "d" row has no left-side data, so its `id`/`value`
+    // (Float) are null-filled with NaN and its `flag` (Bool) with `false`.
+    let d_row = (0..joined.nrow()).find(|&row| str_value(&joined, "group", row) == "d").unwrap();
+    assert!(float_value(&joined, "id", d_row).is_nan());
+    assert!(float_value(&joined, "value", d_row).is_nan());
+    assert_eq!(joined.value("flag", d_row), Some(df_rs::traits::Value::Bool(false)));
+}
+
+#[test]
+fn group_by_and_summarise()
+{
+    let df = people();
+
+    let grouped = df.group_by(&["group"]).unwrap();
+    let summary = grouped.summarise(&[("total", Reducer::Sum("value")), ("n", Reducer::Count)]).unwrap();
+    assert_eq!(summary.nrow(), 3);
+
+    // group "a" is rows 0 and 2 (value 10.0, 30.0); "b" is row 1 (20.0);
+    // "c" is row 3 (40.0).
+    for row in 0..summary.nrow()
+    {
+        let (total, n) = (float_value(&summary, "total", row), float_value(&summary, "n", row));
+        match str_value(&summary, "group", row).as_str()
+        {
+            "a" => assert_eq!((total, n), (40.0, 2.0)),
+            "b" => assert_eq!((total, n), (20.0, 1.0)),
+            "c" => assert_eq!((total, n), (40.0, 1.0)),
+            other => panic!("unexpected group {:?}", other),
+        }
+    }
+
+    // grouping by a numeric column is rejected.
+    assert!(df.group_by(&["value"]).is_err());
+
+    // reducing a non-numeric column is rejected.
+    let grouped = df.group_by(&["group"]).unwrap();
+    assert!(grouped.summarise(&[("bad", Reducer::Sum("group"))]).is_err());
+}
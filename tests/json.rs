@@ -0,0 +1,56 @@
+//! Integration test for `DataFrame::to_json`/`from_json`.
+
+extern crate df_rs;
+
+#[macro_use] extern crate serde_derive;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Record
+{
+    id: usize,
+    name: String,
+}
+
+impl df_rs::traits::Record for Record
+{
+    fn get(&self, name: &'static str) -> Option<df_rs::traits::Value>
+    {
+        use df_rs::traits::Value;
+        match name
+        {
+            "id"   => Some(Value::Float(self.id as f64)),
+            "name" => Some(Value::Str(self.name.clone())),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, name: &'static str, value: df_rs::traits::Value)
+    {
+        use df_rs::traits::Value;
+        match (name, value)
+        {
+            ("id", Value::Float(v)) => self.id = v as usize,
+            ("name", Value::Str(v)) => self.name = v,
+            _ => {}
+        }
+    }
+}
+
+type DataFrame = df_rs::DataFrame<Record>;
+
+#[test]
+fn round_trip() -> serde_json::Result<()>
+{
+    let mut df = DataFrame::new();
+    df.extend([
+        Record { id: 1, name: "alice".to_string() },
+        Record { id: 2, name: "bob".to_string() },
+    ]);
+
+    let json = df.to_json()?;
+    let parsed = DataFrame::from_json(&json)?;
+
+    assert_eq!(parsed, df);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Integration tests for `DataFrame::sample_n`/`sample_frac`/`bootstrap`.
+
+extern crate df_rs;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Record
+{
+    id: usize,
+}
+
+impl df_rs::traits::Record for Record
+{
+    fn get(&self, name: &'static str) -> Option<df_rs::traits::Value>
+    {
+        use df_rs::traits::Value;
+        match name
+        {
+            "id" => Some(Value::Float(self.id as f64)),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, name: &'static str, value: df_rs::traits::Value)
+    {
+        use df_rs::traits::Value;
+        if let ("id", Value::Float(v)) = (name, value)
+        {
+            self.id = v as usize;
+        }
+    }
+}
+
+type DataFrame = df_rs::DataFrame<Record>;
+
+fn people(n: usize) -> DataFrame
+{
+    let mut df = DataFrame::new();
+    df.extend((0..n).map(|id| Record { id }));
+    df
+}
+
+#[test]
+fn sample_n()
+{
+    let df = people(10);
+
+    let sample = df.sample_n(5, 0).unwrap();
+    assert_eq!(sample.len(), 5);
+
+    // the same seed draws the same rows in the same order, regardless of
+    // thread scheduling.
+    assert_eq!(sample, df.sample_n(5, 0).unwrap());
+
+    // a different seed generally draws a different sample.
+    assert_ne!(sample, df.sample_n(5, 1).unwrap());
+
+    // can't sample more rows than the dataframe has without replacement.
+    assert!(df.sample_n(11, 0).is_err());
+}
+
+#[test]
+fn sample_frac()
+{
+    let df = people(10);
+
+    let sample = df.sample_frac(0.5, 0).unwrap();
+    assert_eq!(sample.len(), 5);
+    assert_eq!(sample, df.sample_frac(0.5, 0).unwrap());
+
+    // the fraction must be between 0.0 and 1.0.
+    assert!(df.sample_frac(1.5, 0).is_err());
+    assert!(df.sample_frac(-0.1, 0).is_err());
+}
+
+#[test]
+fn bootstrap()
+{
+    let df = people(10);
+
+    let drawn = df.bootstrap(20, 0).unwrap();
+    assert_eq!(drawn.len(), 20);
+    assert_eq!(drawn, df.bootstrap(20, 0).unwrap());
+    assert_ne!(drawn, df.bootstrap(20, 1).unwrap());
+
+    // nothing to draw from.
+    let empty = DataFrame::new();
+    assert!(empty.bootstrap(1, 0).is_err());
+}